@@ -0,0 +1,312 @@
+//! Non-interactive batch mode: a `clap` CLI that drives the same VLSM engine
+//! as the interactive menu, for use from scripts and CI pipelines.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::net::IpAddr;
+use std::str::FromStr;
+
+use clap::{Parser, ValueEnum};
+use num_bigint::BigUint;
+
+use crate::dhcp::{self, DhcpOptions, GatewayPlacement};
+use crate::ip::{self, IpKind, Ipv4Kind, Ipv6Kind, SubnetInfo};
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum OutputFormat {
+    Table,
+    Csv,
+    Json,
+    IscDhcpd,
+    DhcpJson,
+}
+
+/// Drives the subnet calculator from a file/stdin plan or `--hosts` instead
+/// of the interactive prompts.
+#[derive(Parser, Debug)]
+#[command(name = "subnetcalc", about = "VLSM subnet calculator")]
+pub struct Cli {
+    /// Base network, e.g. 192.168.1.0/24 or 2001:db8::/48
+    #[arg(long)]
+    pub base: Option<String>,
+
+    /// Required host count for a subnet; repeat or comma-separate (e.g. --hosts 50,20,10)
+    #[arg(long, value_delimiter = ',')]
+    pub hosts: Vec<u64>,
+
+    /// Read the subnet plan from a file (or '-' for stdin); each line is `label,host_count`
+    #[arg(long)]
+    pub from_file: Option<String>,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+
+    /// Where to pin the default router within each subnet (used by --format isc-dhcpd/dhcp-json)
+    #[arg(long, value_enum, default_value_t = GatewayPlacement::First)]
+    pub gateway: GatewayPlacement,
+
+    /// DNS servers to advertise in the DHCP scope; repeat or comma-separate
+    #[arg(long, value_delimiter = ',')]
+    pub dns: Vec<String>,
+
+    /// DHCP lease time in seconds
+    #[arg(long, default_value_t = 86400)]
+    pub lease_time: u32,
+
+    /// Exclude the gateway address from the lease pool
+    #[arg(long)]
+    pub reserve_gateway: bool,
+}
+
+impl Cli {
+    fn dhcp_options(&self) -> DhcpOptions {
+        DhcpOptions {
+            gateway: self.gateway,
+            dns_servers: self.dns.clone(),
+            lease_time: self.lease_time,
+            reserve_gateway: self.reserve_gateway,
+        }
+    }
+}
+
+impl Cli {
+    /// A bare invocation (no batch flags at all) should fall through to the
+    /// interactive menu, preserving today's default behavior.
+    pub fn wants_batch_mode(&self) -> bool {
+        self.base.is_some() || self.from_file.is_some() || !self.hosts.is_empty()
+    }
+}
+
+/// One parsed `label,host_count` entry, as produced by `read_plan_lines`;
+/// lines that fail to parse are only counted, not kept.
+#[derive(Debug, PartialEq)]
+struct PlanEntry {
+    label: String,
+    hosts: BigUint,
+}
+
+fn read_plan_lines(reader: impl BufRead) -> (Vec<PlanEntry>, usize) {
+    let mut entries = Vec::new();
+    let mut failures = 0;
+
+    for (i, line) in reader.lines().enumerate() {
+        let line_number = i + 1;
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                eprintln!("warning: line {}: {}, skipping", line_number, e);
+                failures += 1;
+                continue;
+            }
+        };
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut parts = line.splitn(2, ',');
+        let (label, hosts_str) = match (parts.next(), parts.next()) {
+            (Some(label), Some(hosts)) => (label.trim(), hosts.trim()),
+            _ => {
+                eprintln!("warning: line {}: expected `label,host_count`, skipping", line_number);
+                failures += 1;
+                continue;
+            }
+        };
+
+        match BigUint::from_str(hosts_str) {
+            Ok(hosts) if hosts > BigUint::from(0u32) => {
+                entries.push(PlanEntry { label: label.to_string(), hosts });
+            }
+            _ => {
+                eprintln!("warning: line {}: invalid host count '{}', skipping", line_number, hosts_str);
+                failures += 1;
+            }
+        }
+    }
+
+    (entries, failures)
+}
+
+/// Reads the plan from `path`, or from stdin when `path` is `-`.
+fn read_plan_from(path: &str) -> io::Result<(Vec<PlanEntry>, usize)> {
+    if path == "-" {
+        Ok(read_plan_lines(io::stdin().lock()))
+    } else {
+        Ok(read_plan_lines(BufReader::new(File::open(path)?)))
+    }
+}
+
+/// Builds plan entries from `--hosts`, rejecting zero host counts the same
+/// way `read_plan_lines` rejects an invalid `host_count` field.
+fn entries_from_hosts(hosts: &[u64]) -> (Vec<PlanEntry>, usize) {
+    let mut entries = Vec::new();
+    let mut failures = 0;
+    for (i, &hosts) in hosts.iter().enumerate() {
+        if hosts == 0 {
+            eprintln!("warning: --hosts entry {}: invalid host count '0', skipping", i + 1);
+            failures += 1;
+            continue;
+        }
+        entries.push(PlanEntry { label: format!("Subnet {}", i + 1), hosts: BigUint::from(hosts) });
+    }
+    (entries, failures)
+}
+
+fn escape_json(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn render_batch<K: IpKind>(format: OutputFormat, labels: &[String], subnets: &[SubnetInfo<K>], dhcp_opts: &DhcpOptions) {
+    let label_for = |subnet: &SubnetInfo<K>| labels[subnet.subnet_number - 1].as_str();
+
+    match format {
+        OutputFormat::Table => {
+            println!("{:<16} {:<6} {:<20} {:<20} {:<20} {:<20} {:<20}", "Label", "CIDR", "Network", "First Usable", "Last Usable", "Broadcast", "Total Hosts");
+            for subnet in subnets {
+                println!(
+                    "{:<16} /{:<5} {:<20} {:<20} {:<20} {:<20} {:<20}",
+                    label_for(subnet),
+                    subnet.prefix,
+                    subnet.network.to_string(),
+                    subnet.first_usable.to_string(),
+                    subnet.last_usable.to_string(),
+                    subnet.broadcast.to_string(),
+                    subnet.total_hosts,
+                );
+            }
+        }
+        OutputFormat::Csv => {
+            println!("label,network,prefix,mask,first_usable,last_usable,broadcast,total_hosts");
+            for subnet in subnets {
+                println!(
+                    "{},{},{},{},{},{},{},{}",
+                    label_for(subnet),
+                    subnet.network,
+                    subnet.prefix,
+                    subnet.mask,
+                    subnet.first_usable,
+                    subnet.last_usable,
+                    subnet.broadcast,
+                    subnet.total_hosts,
+                );
+            }
+        }
+        OutputFormat::Json => {
+            println!("[");
+            for (i, subnet) in subnets.iter().enumerate() {
+                let comma = if i + 1 == subnets.len() { "" } else { "," };
+                println!(
+                    "  {{\"label\": \"{}\", \"network\": \"{}\", \"prefix\": {}, \"mask\": \"{}\", \"first_usable\": \"{}\", \"last_usable\": \"{}\", \"broadcast\": \"{}\", \"total_hosts\": \"{}\"}}{}",
+                    escape_json(label_for(subnet)),
+                    subnet.network,
+                    subnet.prefix,
+                    subnet.mask,
+                    subnet.first_usable,
+                    subnet.last_usable,
+                    subnet.broadcast,
+                    subnet.total_hosts,
+                    comma,
+                );
+            }
+            println!("]");
+        }
+        OutputFormat::IscDhcpd => dhcp::render_isc_dhcpd(labels, subnets, dhcp_opts),
+        OutputFormat::DhcpJson => dhcp::render_dhcp_json(labels, subnets, dhcp_opts),
+    }
+}
+
+/// Runs batch mode end-to-end and returns the process exit code: nonzero if
+/// the base network was invalid, the plan was empty, a subnet didn't fit, or
+/// any plan line had to be skipped.
+pub fn run(cli: Cli) -> i32 {
+    let Some(base) = cli.base.as_deref() else {
+        eprintln!("error: --base is required in batch mode");
+        return 1;
+    };
+
+    let parts: Vec<&str> = base.split('/').collect();
+    let (Some(ip_part), Some(prefix_part), true) = (parts.first(), parts.get(1), parts.len() == 2) else {
+        eprintln!("error: --base must be IP/CIDR, e.g. 192.168.1.0/24");
+        return 1;
+    };
+
+    let base_ip = match IpAddr::from_str(ip_part) {
+        Ok(ip) => ip,
+        Err(_) => {
+            eprintln!("error: invalid base IP address '{}'", ip_part);
+            return 1;
+        }
+    };
+
+    let max_prefix = if base_ip.is_ipv4() { Ipv4Kind::MAX_PREFIX } else { Ipv6Kind::MAX_PREFIX };
+    let base_prefix: u8 = match prefix_part.parse() {
+        Ok(p) if p <= max_prefix => p,
+        _ => {
+            eprintln!("error: invalid prefix '{}', use 0-{}", prefix_part, max_prefix);
+            return 1;
+        }
+    };
+
+    let (entries, failures) = match &cli.from_file {
+        Some(path) => match read_plan_from(path) {
+            Ok(result) => result,
+            Err(e) => {
+                eprintln!("error: could not read plan from '{}': {}", path, e);
+                return 1;
+            }
+        },
+        None => entries_from_hosts(&cli.hosts),
+    };
+
+    if entries.is_empty() {
+        eprintln!("error: no valid subnet entries in plan");
+        return 1;
+    }
+
+    let labels: Vec<String> = entries.iter().map(|entry| entry.label.clone()).collect();
+    let host_counts: Vec<BigUint> = entries.into_iter().map(|entry| entry.hosts).collect();
+    let dhcp_opts = cli.dhcp_options();
+
+    let result = if base_ip.is_ipv4() {
+        ip::calculate_subnets::<Ipv4Kind>(base_ip, base_prefix, host_counts).map(|subnets| render_batch(cli.format, &labels, &subnets, &dhcp_opts))
+    } else {
+        ip::calculate_subnets::<Ipv6Kind>(base_ip, base_prefix, host_counts).map(|subnets| render_batch(cli.format, &labels, &subnets, &dhcp_opts))
+    };
+
+    match result {
+        Ok(()) if failures > 0 => 1,
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_from_hosts_rejects_zero() {
+        let (entries, failures) = entries_from_hosts(&[0]);
+        assert!(entries.is_empty());
+        assert_eq!(failures, 1);
+    }
+
+    #[test]
+    fn entries_from_hosts_skips_zero_but_keeps_valid_entries() {
+        let (entries, failures) = entries_from_hosts(&[0, 50]);
+        assert_eq!(entries, vec![PlanEntry { label: "Subnet 2".to_string(), hosts: BigUint::from(50u32) }]);
+        assert_eq!(failures, 1);
+    }
+
+    #[test]
+    fn read_plan_lines_rejects_zero_host_count() {
+        let (entries, failures) = read_plan_lines("web,0\ndb,20".as_bytes());
+        assert_eq!(entries, vec![PlanEntry { label: "db".to_string(), hosts: BigUint::from(20u32) }]);
+        assert_eq!(failures, 1);
+    }
+}
@@ -0,0 +1,203 @@
+//! Sibling emitters to `display_subnets`/`render_batch`: turn a computed
+//! allocation into deployable DHCP scope configuration instead of a report.
+
+use crate::ip::{IpKind, RawInt, SubnetInfo};
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum GatewayPlacement {
+    First,
+    Last,
+}
+
+/// Server-config knobs that apply to every scope in an allocation.
+pub struct DhcpOptions {
+    pub gateway: GatewayPlacement,
+    pub dns_servers: Vec<String>,
+    pub lease_time: u32,
+    pub reserve_gateway: bool,
+}
+
+struct Scope {
+    label: String,
+    network: String,
+    mask: String,
+    router: String,
+    pool_start: String,
+    pool_end: String,
+}
+
+fn escape_json(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Places the gateway at `first_usable`/`last_usable` per `opts.gateway`,
+/// then derives the lease pool - shrunk by one address at the gateway's end
+/// when `opts.reserve_gateway` is set so the router isn't handed out.
+///
+/// Returns `None` if the subnet has no usable host range to begin with (e.g.
+/// a /31-or-narrower block where `first_usable > last_usable`) or if
+/// reserving the gateway would push the pool's start past its end, rather
+/// than handing a degenerate `range` out for "deployable" config.
+fn build_scope<K: IpKind>(label: &str, subnet: &SubnetInfo<K>, opts: &DhcpOptions) -> Option<Scope> {
+    let first_raw = K::address_to_raw(subnet.first_usable).expect("first_usable matches K");
+    let last_raw = K::address_to_raw(subnet.last_usable).expect("last_usable matches K");
+    if first_raw > last_raw {
+        return None;
+    }
+
+    let router_raw = match opts.gateway {
+        GatewayPlacement::First => first_raw,
+        GatewayPlacement::Last => last_raw,
+    };
+
+    let (pool_start_raw, pool_end_raw) = if opts.reserve_gateway {
+        match opts.gateway {
+            GatewayPlacement::First => (first_raw.checked_add(K::Raw::ONE)?, last_raw),
+            GatewayPlacement::Last => (first_raw, last_raw.checked_sub(K::Raw::ONE)?),
+        }
+    } else {
+        (first_raw, last_raw)
+    };
+
+    if pool_start_raw > pool_end_raw || pool_start_raw < first_raw || pool_end_raw > last_raw {
+        return None;
+    }
+
+    Some(Scope {
+        label: label.to_string(),
+        network: subnet.network.to_string(),
+        mask: subnet.mask.to_string(),
+        router: K::raw_to_address(router_raw).to_string(),
+        pool_start: K::raw_to_address(pool_start_raw).to_string(),
+        pool_end: K::raw_to_address(pool_end_raw).to_string(),
+    })
+}
+
+/// Builds a `Scope` per subnet, warning on stderr and dropping any subnet
+/// with no usable host range instead of handing a degenerate pool downstream.
+fn build_scopes<K: IpKind>(labels: &[String], subnets: &[SubnetInfo<K>], opts: &DhcpOptions) -> Vec<Scope> {
+    subnets
+        .iter()
+        .filter_map(|subnet| {
+            let label = &labels[subnet.subnet_number - 1];
+            let scope = build_scope(label, subnet, opts);
+            if scope.is_none() {
+                eprintln!("warning: subnet '{}' has no usable host range, skipping its DHCP scope", label);
+            }
+            scope
+        })
+        .collect()
+}
+
+/// Renders one `subnet { ... }` block per subnet, ISC-dhcpd style.
+pub fn render_isc_dhcpd<K: IpKind>(labels: &[String], subnets: &[SubnetInfo<K>], opts: &DhcpOptions) {
+    for scope in build_scopes(labels, subnets, opts) {
+        println!("# {}", scope.label);
+        println!("subnet {} netmask {} {{", scope.network, scope.mask);
+        println!("    option routers {};", scope.router);
+        if !opts.dns_servers.is_empty() {
+            println!("    option domain-name-servers {};", opts.dns_servers.join(", "));
+        }
+        println!("    range {} {};", scope.pool_start, scope.pool_end);
+        println!("    default-lease-time {};", opts.lease_time);
+        println!("    max-lease-time {};", opts.lease_time);
+        println!("}}\n");
+    }
+}
+
+/// Renders the same scopes as a generic JSON array, for config systems that
+/// aren't ISC-dhcpd.
+pub fn render_dhcp_json<K: IpKind>(labels: &[String], subnets: &[SubnetInfo<K>], opts: &DhcpOptions) {
+    let dns_json = opts
+        .dns_servers
+        .iter()
+        .map(|dns| format!("\"{}\"", escape_json(dns)))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let scopes = build_scopes(labels, subnets, opts);
+    println!("[");
+    for (i, scope) in scopes.iter().enumerate() {
+        let comma = if i + 1 == scopes.len() { "" } else { "," };
+        println!(
+            "  {{\"label\": \"{}\", \"network\": \"{}\", \"mask\": \"{}\", \"router\": \"{}\", \"dns_servers\": [{}], \"range\": {{\"start\": \"{}\", \"end\": \"{}\"}}, \"lease_time\": {}}}{}",
+            escape_json(&scope.label),
+            scope.network,
+            scope.mask,
+            scope.router,
+            dns_json,
+            scope.pool_start,
+            scope.pool_end,
+            opts.lease_time,
+            comma,
+        );
+    }
+    println!("]");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ip::{Ipv4Kind, SubnetInfo};
+    use num_bigint::BigUint;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    fn opts(gateway: GatewayPlacement, reserve_gateway: bool) -> DhcpOptions {
+        DhcpOptions { gateway, dns_servers: Vec::new(), lease_time: 86400, reserve_gateway }
+    }
+
+    fn ip(a: u8, b: u8, c: u8, d: u8) -> IpAddr {
+        IpAddr::V4(Ipv4Addr::new(a, b, c, d))
+    }
+
+    #[test]
+    fn build_scope_rejects_subnet_with_no_usable_host_range() {
+        // A /31-style block where first_usable > last_usable (no usable hosts).
+        let subnet = SubnetInfo::<Ipv4Kind>::for_test(
+            ip(10, 0, 0, 0),
+            31,
+            ip(255, 255, 255, 254),
+            ip(10, 0, 0, 1),
+            ip(10, 0, 0, 0),
+            ip(10, 0, 0, 1),
+            BigUint::from(0u32),
+        );
+
+        assert!(build_scope("degenerate", &subnet, &opts(GatewayPlacement::First, false)).is_none());
+    }
+
+    #[test]
+    fn build_scope_rejects_reserve_gateway_that_exhausts_a_single_address_pool() {
+        // A usable range of exactly one address: reserving the gateway at
+        // `first_usable` would push the pool start past its end.
+        let subnet = SubnetInfo::<Ipv4Kind>::for_test(
+            ip(10, 0, 0, 0),
+            30,
+            ip(255, 255, 255, 252),
+            ip(10, 0, 0, 1),
+            ip(10, 0, 0, 1),
+            ip(10, 0, 0, 3),
+            BigUint::from(1u32),
+        );
+
+        assert!(build_scope("exhausted", &subnet, &opts(GatewayPlacement::First, true)).is_none());
+    }
+
+    #[test]
+    fn build_scope_accepts_reserve_gateway_on_a_normal_pool() {
+        let subnet = SubnetInfo::<Ipv4Kind>::for_test(
+            ip(10, 0, 0, 0),
+            29,
+            ip(255, 255, 255, 248),
+            ip(10, 0, 0, 1),
+            ip(10, 0, 0, 6),
+            ip(10, 0, 0, 7),
+            BigUint::from(6u32),
+        );
+
+        let scope = build_scope("normal", &subnet, &opts(GatewayPlacement::First, true)).expect("pool still has room");
+        assert_eq!(scope.router, "10.0.0.1");
+        assert_eq!(scope.pool_start, "10.0.0.2");
+        assert_eq!(scope.pool_end, "10.0.0.6");
+    }
+}
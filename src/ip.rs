@@ -0,0 +1,285 @@
+//! Address-width abstraction for the VLSM subnet-calculator engine.
+//!
+//! The prefix-aggregation and free-space tools in `main.rs` are IPv4-only by
+//! design (they mirror `aggregate6`-style tooling and stay on plain `u32`
+//! arithmetic). This module generalizes the VLSM engine itself -
+//! `calculate_subnets`, `SubnetInfo` and friends - over an `IpKind` so it
+//! runs unmodified for both IPv4 (`/0..=/32`) and IPv6 (`/0..=/128`).
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use num_bigint::BigUint;
+
+/// The unsigned integer an `IpKind` uses to represent a raw address.
+///
+/// Implemented for `u32` (IPv4) and `u128` (IPv6); every bitwise op the
+/// engine needs (mask construction, network/broadcast derivation) is
+/// expressible purely in terms of this trait, so `calculate_subnets` never
+/// has to know which width it's working with.
+pub trait RawInt:
+    Copy
+    + Ord
+    + std::ops::Not<Output = Self>
+    + std::ops::BitAnd<Output = Self>
+    + std::ops::BitOr<Output = Self>
+    + std::ops::Shl<u32, Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn to_biguint(self) -> BigUint;
+}
+
+macro_rules! impl_raw_int {
+    ($t:ty) => {
+        impl RawInt for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_add(self, rhs)
+            }
+
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_sub(self, rhs)
+            }
+
+            fn to_biguint(self) -> BigUint {
+                BigUint::from(self)
+            }
+        }
+    };
+}
+
+impl_raw_int!(u32);
+impl_raw_int!(u128);
+
+/// An address family: its raw integer width and how to move between that
+/// integer and `std::net::IpAddr`.
+pub trait IpKind: Copy {
+    type Raw: RawInt;
+    const MAX_PREFIX: u8;
+    const NAME: &'static str;
+
+    /// Extracts this family's raw representation, or `None` if `addr` is
+    /// the other family.
+    fn address_to_raw(addr: IpAddr) -> Option<Self::Raw>;
+    fn raw_to_address(raw: Self::Raw) -> IpAddr;
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Ipv4Kind;
+
+impl IpKind for Ipv4Kind {
+    type Raw = u32;
+    const MAX_PREFIX: u8 = 32;
+    const NAME: &'static str = "IPv4";
+
+    fn address_to_raw(addr: IpAddr) -> Option<u32> {
+        match addr {
+            IpAddr::V4(ip) => Some(u32::from_be_bytes(ip.octets())),
+            IpAddr::V6(_) => None,
+        }
+    }
+
+    fn raw_to_address(raw: u32) -> IpAddr {
+        IpAddr::V4(Ipv4Addr::from(raw.to_be_bytes()))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Ipv6Kind;
+
+impl IpKind for Ipv6Kind {
+    type Raw = u128;
+    const MAX_PREFIX: u8 = 128;
+    const NAME: &'static str = "IPv6";
+
+    fn address_to_raw(addr: IpAddr) -> Option<u128> {
+        match addr {
+            IpAddr::V6(ip) => Some(u128::from_be_bytes(ip.octets())),
+            IpAddr::V4(_) => None,
+        }
+    }
+
+    fn raw_to_address(raw: u128) -> IpAddr {
+        IpAddr::V6(Ipv6Addr::from(raw.to_be_bytes()))
+    }
+}
+
+/// Builds the `/prefix` mask for address family `K`, e.g. `subnet_mask::<Ipv4Kind>(24)`.
+pub fn subnet_mask<K: IpKind>(prefix: u8) -> K::Raw {
+    if prefix == 0 {
+        K::Raw::ZERO
+    } else {
+        !K::Raw::ZERO << (K::MAX_PREFIX as u32 - prefix as u32)
+    }
+}
+
+/// Longest prefix length for `K` whose block holds `hosts` usable addresses
+/// (plus network and broadcast). `hosts` is a `BigUint` since an IPv6 allocation
+/// can be asked to hold far more hosts than fit in a `u128`. If no block of
+/// `K` is that large, the returned prefix names a block too small for
+/// `hosts`; `calculate_subnets` checks the block's size and rejects that case.
+pub fn calculate_prefix_for_hosts<K: IpKind>(hosts: &BigUint) -> u8 {
+    let total_needed = hosts + 2u32;
+    let bits_needed = total_needed.bits() as u8 - if total_needed.count_ones() == 1 { 1 } else { 0 };
+    K::MAX_PREFIX.saturating_sub(bits_needed)
+}
+
+#[derive(Debug)]
+pub struct SubnetInfo<K: IpKind> {
+    pub subnet_number: usize,
+    pub required_hosts: BigUint,
+    pub network: IpAddr,
+    pub prefix: u8,
+    pub mask: IpAddr,
+    pub first_usable: IpAddr,
+    pub last_usable: IpAddr,
+    pub broadcast: IpAddr,
+    pub total_hosts: BigUint,
+    _kind: std::marker::PhantomData<K>,
+}
+
+#[cfg(test)]
+impl<K: IpKind> SubnetInfo<K> {
+    /// Test-only constructor so other modules' tests (e.g. `dhcp`'s
+    /// `build_scope` tests) can build degenerate or boundary host ranges
+    /// without going through a real `calculate_subnets` allocation.
+    pub(crate) fn for_test(
+        network: IpAddr,
+        prefix: u8,
+        mask: IpAddr,
+        first_usable: IpAddr,
+        last_usable: IpAddr,
+        broadcast: IpAddr,
+        total_hosts: BigUint,
+    ) -> Self {
+        SubnetInfo {
+            subnet_number: 1,
+            required_hosts: BigUint::from(0u32),
+            network,
+            prefix,
+            mask,
+            first_usable,
+            last_usable,
+            broadcast,
+            total_hosts,
+            _kind: std::marker::PhantomData,
+        }
+    }
+}
+
+/// Generic VLSM allocator: carves `host_counts` largest-first out of
+/// `base_ip/base_prefix`, the same way the original `u32`-only engine did,
+/// but over whichever address family `K` is.
+pub fn calculate_subnets<K: IpKind>(
+    base_ip: IpAddr,
+    base_prefix: u8,
+    host_counts: Vec<BigUint>,
+) -> Result<Vec<SubnetInfo<K>>, String> {
+    let base_raw = K::address_to_raw(base_ip)
+        .ok_or_else(|| format!("{} expects an {} base address", K::NAME, K::NAME))?;
+    let base_mask = subnet_mask::<K>(base_prefix);
+    let base_network = base_raw & base_mask;
+    let base_broadcast = base_network | !base_mask;
+
+    let mut sorted: Vec<(usize, BigUint)> = host_counts.into_iter().enumerate().collect();
+    sorted.sort_by(|a, b| b.1.cmp(&a.1));
+
+    let mut subnets = Vec::new();
+    let mut current_network = Some(base_network);
+
+    for (original_index, hosts) in sorted {
+        let prefix = calculate_prefix_for_hosts::<K>(&hosts);
+        if prefix < base_prefix {
+            return Err(format!("Subnet {} needs /{} but base is /{}", original_index + 1, prefix, base_prefix));
+        }
+
+        // `None` once a previous subnet ended at the top of the address space.
+        let start = current_network
+            .ok_or_else(|| format!("Subnet {} does not fit in base network", original_index + 1))?;
+        let mask = subnet_mask::<K>(prefix);
+        let network = start & mask;
+        let broadcast = network | !mask;
+
+        if broadcast > base_broadcast {
+            return Err(format!("Subnet {} does not fit in base network", original_index + 1));
+        }
+
+        let total_hosts = broadcast.to_biguint() - network.to_biguint() + 1u32 - 2u32;
+        if total_hosts < hosts {
+            return Err(format!("Subnet {} does not fit in base network", original_index + 1));
+        }
+        let first_usable = network
+            .checked_add(K::Raw::ONE)
+            .ok_or_else(|| format!("Subnet {} has no usable host addresses", original_index + 1))?;
+        let last_usable = broadcast
+            .checked_sub(K::Raw::ONE)
+            .ok_or_else(|| format!("Subnet {} has no usable host addresses", original_index + 1))?;
+
+        subnets.push((original_index, SubnetInfo {
+            subnet_number: original_index + 1,
+            required_hosts: hosts,
+            network: K::raw_to_address(network),
+            prefix,
+            mask: K::raw_to_address(mask),
+            first_usable: K::raw_to_address(first_usable),
+            last_usable: K::raw_to_address(last_usable),
+            broadcast: K::raw_to_address(broadcast),
+            total_hosts,
+            _kind: std::marker::PhantomData,
+        }));
+
+        current_network = broadcast.checked_add(K::Raw::ONE);
+    }
+
+    subnets.sort_by_key(|(_, info)| match info.network {
+        IpAddr::V4(ip) => BigUint::from(u32::from_be_bytes(ip.octets())),
+        IpAddr::V6(ip) => BigUint::from(u128::from_be_bytes(ip.octets())),
+    });
+    Ok(subnets.into_iter().map(|(_, info)| info).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn calculate_subnets_ipv4_matches_classic_vlsm() {
+        let base = IpAddr::from_str("192.168.1.0").unwrap();
+        let subnets = calculate_subnets::<Ipv4Kind>(base, 24, vec![BigUint::from(100u32), BigUint::from(50u32)]).unwrap();
+
+        assert_eq!(subnets[0].network, IpAddr::from_str("192.168.1.0").unwrap());
+        assert_eq!(subnets[0].prefix, 25);
+        assert_eq!(subnets[0].total_hosts, BigUint::from(126u32));
+
+        assert_eq!(subnets[1].network, IpAddr::from_str("192.168.1.128").unwrap());
+        assert_eq!(subnets[1].prefix, 26);
+        assert_eq!(subnets[1].total_hosts, BigUint::from(62u32));
+    }
+
+    #[test]
+    fn calculate_subnets_ipv6_handles_host_counts_beyond_u64() {
+        let base = IpAddr::from_str("2001:db8::").unwrap();
+        let hosts = BigUint::from(1u32) << 70u32; // far beyond u64::MAX
+        let subnets = calculate_subnets::<Ipv6Kind>(base, 48, vec![hosts.clone()]).unwrap();
+
+        assert_eq!(subnets[0].prefix, 57);
+        assert_eq!(subnets[0].total_hosts, (BigUint::from(1u32) << 71u32) - 2u32);
+    }
+
+    #[test]
+    fn calculate_subnets_rejects_base_address_family_mismatch() {
+        let base = IpAddr::from_str("2001:db8::").unwrap();
+        assert!(calculate_subnets::<Ipv4Kind>(base, 24, vec![BigUint::from(10u32)]).is_err());
+    }
+
+    #[test]
+    fn calculate_subnets_rejects_subnet_that_does_not_fit() {
+        let base = IpAddr::from_str("192.168.1.0").unwrap();
+        assert!(calculate_subnets::<Ipv4Kind>(base, 24, vec![BigUint::from(1000u32)]).is_err());
+    }
+}
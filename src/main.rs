@@ -1,7 +1,15 @@
-use std::net::Ipv4Addr;
+use std::net::{IpAddr, Ipv4Addr};
 use std::str::FromStr;
 use std::io::{self, Write};
+use std::process::ExitCode;
+use clap::Parser;
 use colored::*;
+use num_bigint::BigUint;
+
+mod batch;
+mod dhcp;
+mod ip;
+use ip::{IpKind, Ipv4Kind, Ipv6Kind, SubnetInfo};
 
 fn ipv4_to_u32(ip: Ipv4Addr) -> u32 {
     u32::from_be_bytes(ip.octets())
@@ -15,74 +23,156 @@ fn subnet_mask(prefix: u8) -> u32 {
     if prefix == 0 { 0 } else { (!0u32) << (32 - prefix) }
 }
 
-fn calculate_prefix_for_hosts(hosts: u32) -> u8 {
-    let total_needed = hosts + 2;
-    let bits_needed = (total_needed as f64).log2().ceil() as u8;
-    32 - bits_needed
-}
+// `SubnetInfo`/`calculate_subnets` live in `ip` now, generic over `IpKind`
+// so the VLSM engine used below runs for both IPv4 and IPv6 bases. The
+// aggregate/free-space tools below stay on the plain `u32` helpers above,
+// since they're IPv4-only by design.
 
-#[derive(Debug)]
-struct SubnetInfo {
-    subnet_number: usize,
-    required_hosts: u32,
-    network: Ipv4Addr,
-    prefix: u8,
-    mask: Ipv4Addr,
-    first_usable: Ipv4Addr,
-    last_usable: Ipv4Addr,
-    broadcast: Ipv4Addr,
-    total_hosts: u32,
+/// Returns true if `b` (network/prefix) is fully contained within `a`.
+fn is_covered_by(a: (u32, u8), b: (u32, u8)) -> bool {
+    let (a_network, a_prefix) = a;
+    let (b_network, b_prefix) = b;
+    a_prefix <= b_prefix && (b_network & subnet_mask(a_prefix)) == a_network
 }
 
-fn calculate_subnets(base_ip: Ipv4Addr, base_prefix: u8, host_counts: Vec<u32>) -> Result<Vec<SubnetInfo>, String> {
-    let base_mask_num = subnet_mask(base_prefix);
-    let base_network_num = ipv4_to_u32(base_ip) & base_mask_num;
-    let base_broadcast = base_network_num | !base_mask_num;
-
-    let mut sorted: Vec<(usize, u32)> = host_counts.iter().enumerate().map(|(i, &h)| (i, h)).collect();
-    sorted.sort_by(|a, b| b.1.cmp(&a.1));
+/// Collapses a list of `(network, prefix)` pairs into the minimal set of CIDR
+/// blocks that covers the same address space, the way `aggregate6` does.
+///
+/// Input addresses are first normalized to their network address. Blocks
+/// that are already covered by a preceding (sorted) block are dropped, then
+/// adjacent, aligned sibling pairs are merged into their shared parent
+/// prefix. Dropping and merging repeats to a fixpoint, since a merge can
+/// expose a new covering relationship or a new sibling pair.
+fn aggregate_prefixes(networks: Vec<(Ipv4Addr, u8)>) -> Vec<(Ipv4Addr, u8)> {
+    let mut blocks: Vec<(u32, u8)> = networks
+        .into_iter()
+        .map(|(ip, prefix)| (ipv4_to_u32(ip) & subnet_mask(prefix), prefix))
+        .collect();
 
-    let mut subnets = Vec::new();
-    let mut current_network = base_network_num;
+    loop {
+        blocks.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(&b.1)));
 
-    for (original_index, hosts) in sorted {
-        let prefix = calculate_prefix_for_hosts(hosts);
-        if prefix < base_prefix {
-            return Err(format!("Subnet {} needs /{} but base is /{}", original_index + 1, prefix, base_prefix));
+        let mut deduped: Vec<(u32, u8)> = Vec::with_capacity(blocks.len());
+        for block in blocks {
+            if let Some(&last) = deduped.last() {
+                if is_covered_by(last, block) {
+                    continue;
+                }
+            }
+            deduped.push(block);
         }
 
-        let mask_num = subnet_mask(prefix);
-        let network = current_network & mask_num;
-        let broadcast = network | !mask_num;
+        let mut merged: Vec<(u32, u8)> = Vec::with_capacity(deduped.len());
+        let mut changed = false;
+        let mut i = 0;
+        while i < deduped.len() {
+            if i + 1 < deduped.len() {
+                let (network, prefix) = deduped[i];
+                let (next_network, next_prefix) = deduped[i + 1];
+                if prefix > 0 && prefix == next_prefix {
+                    let parent_prefix = prefix - 1;
+                    let sibling_bit = 1u32 << (32 - prefix);
+                    let siblings = network & subnet_mask(parent_prefix) == next_network & subnet_mask(parent_prefix)
+                        && network & sibling_bit == 0;
+                    if siblings {
+                        merged.push((network, parent_prefix));
+                        changed = true;
+                        i += 2;
+                        continue;
+                    }
+                }
+            }
+            merged.push(deduped[i]);
+            i += 1;
+        }
 
-        if broadcast > base_broadcast {
-            return Err(format!("Subnet {} does not fit in base network", original_index + 1));
+        blocks = merged;
+        if !changed {
+            break;
         }
+    }
 
-        let total_hosts = (broadcast - network + 1) - 2;
-        let first_usable = u32_to_ipv4(network + 1);
-        let last_usable = u32_to_ipv4(broadcast - 1);
+    blocks
+        .into_iter()
+        .map(|(network, prefix)| (u32_to_ipv4(network), prefix))
+        .collect()
+}
+
+/// Returns the largest CIDR block that starts at `lo`, fits `lo`'s alignment
+/// and stays within `hi`; `free_blocks` calls it repeatedly to decompose a
+/// range `[lo, hi]` into the fewest aligned blocks.
+fn largest_aligned_block(lo: u32, hi: u32) -> (u32, u8) {
+    let align_bits = if lo == 0 { 32 } else { lo.trailing_zeros() };
+    let range_size = u64::from(hi) - u64::from(lo) + 1;
+    let range_bits = 63 - range_size.leading_zeros();
 
-        subnets.push((original_index, SubnetInfo {
-            subnet_number: original_index + 1,
-            required_hosts: hosts,
-            network: u32_to_ipv4(network),
-            prefix,
-            mask: u32_to_ipv4(mask_num),
-            first_usable,
-            last_usable,
-            broadcast: u32_to_ipv4(broadcast),
-            total_hosts,
-        }));
+    let size_bits = align_bits.min(range_bits);
+    (lo, 32 - size_bits as u8)
+}
 
-        current_network = broadcast + 1;
+/// Returns the unallocated address ranges within `base_ip/base_prefix`,
+/// expressed as the fewest possible aligned CIDR blocks.
+///
+/// `used` need not be sorted or non-overlapping; blocks are sorted by start
+/// address and a cursor walks the base range, treating the gap before each
+/// used block (and the gap after the last one) as free. A used block that
+/// falls outside the base range entirely is skipped with a warning; one that
+/// only partly overlaps is clamped to the base range first, so the cursor
+/// walk (and the CIDR blocks it emits) never strays outside `base_ip/base_prefix`.
+fn free_blocks(base_ip: Ipv4Addr, base_prefix: u8, used: Vec<(Ipv4Addr, u8)>) -> Vec<(Ipv4Addr, u8)> {
+    let base_mask_num = subnet_mask(base_prefix);
+    let base_network_num = ipv4_to_u32(base_ip) & base_mask_num;
+    let base_broadcast = base_network_num | !base_mask_num;
+
+    let mut used_ranges: Vec<(u32, u32)> = used
+        .into_iter()
+        .filter_map(|(ip, prefix)| {
+            let mask = subnet_mask(prefix);
+            let network = ipv4_to_u32(ip) & mask;
+            let broadcast = network | !mask;
+            if broadcast < base_network_num || network > base_broadcast {
+                eprintln!(
+                    "warning: used subnet {}/{} falls entirely outside {}/{}, skipping",
+                    ip, prefix, base_ip, base_prefix
+                );
+                return None;
+            }
+            Some((network.max(base_network_num), broadcast.min(base_broadcast)))
+        })
+        .collect();
+    used_ranges.sort_by_key(|&(network, _)| network);
+
+    // `cursor` is widened to u64 because a used block can end at the very
+    // top of the address space (e.g. 255.255.255.255/32), where `broadcast
+    // + 1` would overflow a u32.
+    let mut free_ranges = Vec::new();
+    let mut cursor = u64::from(base_network_num);
+    for (network, broadcast) in used_ranges {
+        let (network, broadcast) = (u64::from(network), u64::from(broadcast));
+        if network > cursor {
+            free_ranges.push((cursor as u32, (network - 1) as u32));
+        }
+        cursor = cursor.max(broadcast + 1);
+    }
+    if cursor <= u64::from(base_broadcast) {
+        free_ranges.push((cursor as u32, base_broadcast));
+    }
+
+    let mut blocks = Vec::new();
+    for (lo, hi) in free_ranges {
+        let mut cursor = u64::from(lo);
+        let hi = u64::from(hi);
+        while cursor <= hi {
+            let (block_network, prefix) = largest_aligned_block(cursor as u32, hi as u32);
+            blocks.push((u32_to_ipv4(block_network), prefix));
+            cursor += 1u64 << (32 - prefix);
+        }
     }
 
-    subnets.sort_by_key(|(_, info)| ipv4_to_u32(info.network));
-    Ok(subnets.into_iter().map(|(_, info)| info).collect())
+    blocks
 }
 
-fn display_subnets(base_ip: Ipv4Addr, base_prefix: u8, subnets: Vec<SubnetInfo>) {
+fn display_subnets<K: IpKind>(base_ip: IpAddr, base_prefix: u8, subnets: Vec<SubnetInfo<K>>) {
     println!("\n{}", "═══════════════════════════════════════════════════════════".bright_cyan());
     println!("{} {}/{}", "Base Network:".bright_cyan().bold(), base_ip.to_string().bright_green(), base_prefix.to_string().bright_green());
     println!("{}", "═══════════════════════════════════════════════════════════".bright_cyan());
@@ -103,50 +193,170 @@ fn display_subnets(base_ip: Ipv4Addr, base_prefix: u8, subnets: Vec<SubnetInfo>)
     println!("\n{}", "═══════════════════════════════════════════════════════════".bright_cyan());
 }
 
-fn main() {
-    println!("{}", "╔═══════════════════════════════════════════════════════╗".bright_cyan());
-    println!("{}", "║                   Subnet Calculator                   ║".bright_cyan().bold());
-    println!("{}", "╚═══════════════════════════════════════════════════════╝".bright_cyan());
-    
+fn display_aggregated(inputs: &[(Ipv4Addr, u8)], aggregated: &[(Ipv4Addr, u8)]) {
+    println!("\n{}", "═══════════════════════════════════════════════════════════".bright_cyan());
+    println!("{} {} {} {}", "Aggregated".bright_cyan().bold(), inputs.len().to_string().bright_green(), "routes into".bright_cyan().bold(), aggregated.len().to_string().bright_green());
+    println!("{}", "═══════════════════════════════════════════════════════════".bright_cyan());
+
+    for (network, prefix) in aggregated {
+        println!("{:<20} /{}", network.to_string().bright_green(), prefix.to_string().bright_green());
+    }
+
+    println!("\n{}", "═══════════════════════════════════════════════════════════".bright_cyan());
+}
+
+/// Reads `ip/prefix` lines from stdin until a blank line.
+fn read_network_list() -> Vec<(Ipv4Addr, u8)> {
+    let mut networks = Vec::new();
     loop {
-        println!("\n{}", "Enter base network (IP/CIDR, e.g., 192.168.1.0/24) or 'exit':".blue());
         let mut input = String::new();
         io::stdout().flush().unwrap();
         io::stdin().read_line(&mut input).expect("Failed to read input");
         let input = input.trim();
-        
-        if input.eq_ignore_ascii_case("exit") {
-            println!("{}", "Exiting...".bright_yellow());
+
+        if input.is_empty() {
             break;
         }
-        
+
         let parts: Vec<&str> = input.split('/').collect();
         if parts.len() != 2 {
             println!("{}", "Invalid format! Use xxx.xxx.xxx.xxx/yy\n".bright_red());
             continue;
         }
-        
-        let base_ip = match Ipv4Addr::from_str(parts[0]) {
+
+        let ip = match Ipv4Addr::from_str(parts[0]) {
             Ok(ip) => ip,
             Err(_) => {
                 println!("{}", "Invalid IP address!\n".bright_red());
                 continue;
             }
         };
-        
-        let base_prefix: u8 = match parts[1].parse() {
+
+        let prefix: u8 = match parts[1].parse() {
             Ok(p) if p <= 32 => p,
             _ => {
                 println!("{}", "Invalid prefix! Use 0-32.\n".bright_red());
                 continue;
             }
         };
+
+        networks.push((ip, prefix));
+    }
+
+    networks
+}
+
+/// Reads `ip/prefix` lines from stdin until a blank line, then prints the
+/// minimal covering set via `aggregate_prefixes`.
+fn run_aggregate_prefixes_menu() {
+    println!("\n{}", "Enter one network per line as IP/CIDR (e.g., 10.0.0.0/24). Blank line to finish:".blue());
+
+    let networks = read_network_list();
+    if networks.is_empty() {
+        println!("{}", "No networks entered.\n".bright_red());
+        return;
+    }
+
+    let aggregated = aggregate_prefixes(networks.clone());
+    display_aggregated(&networks, &aggregated);
+}
+
+fn display_free_blocks(base_ip: Ipv4Addr, base_prefix: u8, free: &[(Ipv4Addr, u8)]) {
+    println!("\n{}", "═══════════════════════════════════════════════════════════".bright_cyan());
+    println!("{} {}/{}", "Base Network:".bright_cyan().bold(), base_ip.to_string().bright_green(), base_prefix.to_string().bright_green());
+    println!("{}", "═══════════════════════════════════════════════════════════".bright_cyan());
+
+    if free.is_empty() {
+        println!("\n{}", "No free space remaining.".bright_red());
+    } else {
+        println!("\n{}", "Free blocks:".bright_yellow().bold());
+        for (network, prefix) in free {
+            println!("{:<20} /{}", network.to_string().bright_green(), prefix.to_string().bright_green());
+        }
+    }
+
+    println!("\n{}", "═══════════════════════════════════════════════════════════".bright_cyan());
+}
+
+/// Reads a base network and its already-used child subnets, then prints the
+/// unallocated gaps via `free_blocks`.
+fn run_free_blocks_menu() {
+    println!("\n{}", "Enter base network (IP/CIDR, e.g., 10.0.0.0/16):".blue());
+    let mut input = String::new();
+    io::stdout().flush().unwrap();
+    io::stdin().read_line(&mut input).expect("Failed to read input");
+    let input = input.trim();
+
+    let parts: Vec<&str> = input.split('/').collect();
+    if parts.len() != 2 {
+        println!("{}", "Invalid format! Use xxx.xxx.xxx.xxx/yy\n".bright_red());
+        return;
+    }
+
+    let base_ip = match Ipv4Addr::from_str(parts[0]) {
+        Ok(ip) => ip,
+        Err(_) => {
+            println!("{}", "Invalid IP address!\n".bright_red());
+            return;
+        }
+    };
+
+    let base_prefix: u8 = match parts[1].parse() {
+        Ok(p) if p <= 32 => p,
+        _ => {
+            println!("{}", "Invalid prefix! Use 0-32.\n".bright_red());
+            return;
+        }
+    };
+
+    println!("\n{}", "Enter already-used subnets, one per line as IP/CIDR. Blank line to finish:".blue());
+    let used = read_network_list();
+
+    let free = free_blocks(base_ip, base_prefix, used);
+    display_free_blocks(base_ip, base_prefix, &free);
+}
+
+/// Runs the original interactive VLSM subnet-calculator flow.
+fn run_subnet_calculator_menu() {
+    loop {
+        println!("\n{}", "Enter base network (IP/CIDR, e.g., 192.168.1.0/24) or 'back':".blue());
+        let mut input = String::new();
+        io::stdout().flush().unwrap();
+        io::stdin().read_line(&mut input).expect("Failed to read input");
+        let input = input.trim();
+
+        if input.eq_ignore_ascii_case("back") || input.eq_ignore_ascii_case("exit") {
+            break;
+        }
         
+        let parts: Vec<&str> = input.split('/').collect();
+        if parts.len() != 2 {
+            println!("{}", "Invalid format! Use xxx.xxx.xxx.xxx/yy or an IPv6 address/prefix\n".bright_red());
+            continue;
+        }
+
+        let base_ip = match IpAddr::from_str(parts[0]) {
+            Ok(ip) => ip,
+            Err(_) => {
+                println!("{}", "Invalid IP address!\n".bright_red());
+                continue;
+            }
+        };
+
+        let max_prefix = if base_ip.is_ipv4() { Ipv4Kind::MAX_PREFIX } else { Ipv6Kind::MAX_PREFIX };
+        let base_prefix: u8 = match parts[1].parse() {
+            Ok(p) if p <= max_prefix => p,
+            _ => {
+                println!("{} Use 0-{}.\n", "Invalid prefix!".bright_red(), max_prefix);
+                continue;
+            }
+        };
+
         println!("\n{}", "Enter number of subnets to create:".blue());
         let mut num_input = String::new();
         io::stdout().flush().unwrap();
         io::stdin().read_line(&mut num_input).expect("Failed to read input");
-        
+
         let num_subnets: usize = match num_input.trim().parse() {
             Ok(n) if n > 0 => n,
             _ => {
@@ -154,16 +364,16 @@ fn main() {
                 continue;
             }
         };
-        
+
         let mut host_counts = Vec::new();
         for i in 1..=num_subnets {
             println!("\n{} {}:", "Enter required hosts for subnet".blue(), i.to_string().bright_yellow());
             let mut hosts_input = String::new();
             io::stdout().flush().unwrap();
             io::stdin().read_line(&mut hosts_input).expect("Failed to read input");
-            
-            let hosts: u32 = match hosts_input.trim().parse() {
-                Ok(h) if h > 0 => h,
+
+            let hosts: BigUint = match hosts_input.trim().parse() {
+                Ok(h) if h > BigUint::from(0u32) => h,
                 _ => {
                     println!("{}", "Invalid host count!\n".bright_red());
                     continue;
@@ -171,10 +381,124 @@ fn main() {
             };
             host_counts.push(hosts);
         }
-        
-        match calculate_subnets(base_ip, base_prefix, host_counts) {
-            Ok(subnets) => return display_subnets(base_ip, base_prefix, subnets),
-            Err(e) => println!("{} {}\n", "Error:".bright_red().bold(), e.bright_red()),
+
+        let result = if base_ip.is_ipv4() {
+            ip::calculate_subnets::<Ipv4Kind>(base_ip, base_prefix, host_counts)
+                .map(|subnets| display_subnets(base_ip, base_prefix, subnets))
+        } else {
+            ip::calculate_subnets::<Ipv6Kind>(base_ip, base_prefix, host_counts)
+                .map(|subnets| display_subnets(base_ip, base_prefix, subnets))
+        };
+
+        if let Err(e) = result {
+            println!("{} {}\n", "Error:".bright_red().bold(), e.bright_red());
+        }
+    }
+}
+
+fn run_interactive() {
+    println!("{}", "╔═══════════════════════════════════════════════════════╗".bright_cyan());
+    println!("{}", "║                   Subnet Calculator                   ║".bright_cyan().bold());
+    println!("{}", "╚═══════════════════════════════════════════════════════╝".bright_cyan());
+
+    loop {
+        println!("\n{}", "[1] Calculate subnets (VLSM)".blue());
+        println!("{}", "[2] Aggregate prefixes".blue());
+        println!("{}", "[3] Free-space / subnet-inversion calculator".blue());
+        println!("{}", "[4] Exit".blue());
+        println!("{}", "Choose an option:".blue());
+
+        let mut choice = String::new();
+        io::stdout().flush().unwrap();
+        io::stdin().read_line(&mut choice).expect("Failed to read input");
+
+        match choice.trim() {
+            "1" => run_subnet_calculator_menu(),
+            "2" => run_aggregate_prefixes_menu(),
+            "3" => run_free_blocks_menu(),
+            "4" => {
+                println!("{}", "Exiting...".bright_yellow());
+                break;
+            }
+            _ => println!("{}", "Invalid option!\n".bright_red()),
         }
     }
+}
+
+fn main() -> ExitCode {
+    let cli = batch::Cli::parse();
+
+    if cli.wants_batch_mode() {
+        ExitCode::from(batch::run(cli) as u8)
+    } else {
+        run_interactive();
+        ExitCode::SUCCESS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aggregate_prefixes_merges_adjacent_siblings() {
+        let networks = vec![(Ipv4Addr::new(10, 0, 0, 0), 25), (Ipv4Addr::new(10, 0, 0, 128), 25)];
+        assert_eq!(aggregate_prefixes(networks), vec![(Ipv4Addr::new(10, 0, 0, 0), 24)]);
+    }
+
+    #[test]
+    fn aggregate_prefixes_drops_covered_blocks() {
+        let networks = vec![(Ipv4Addr::new(10, 0, 0, 0), 24), (Ipv4Addr::new(10, 0, 0, 64), 26)];
+        assert_eq!(aggregate_prefixes(networks), vec![(Ipv4Addr::new(10, 0, 0, 0), 24)]);
+    }
+
+    #[test]
+    fn aggregate_prefixes_leaves_non_siblings_alone() {
+        let networks = vec![(Ipv4Addr::new(10, 0, 0, 0), 25), (Ipv4Addr::new(10, 0, 1, 0), 25)];
+        let mut result = aggregate_prefixes(networks.clone());
+        result.sort_by_key(|&(ip, _)| ip);
+        let mut expected = networks;
+        expected.sort_by_key(|&(ip, _)| ip);
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn free_blocks_finds_space_after_used_subnet() {
+        let used = vec![(Ipv4Addr::new(10, 0, 0, 0), 26)];
+        assert_eq!(
+            free_blocks(Ipv4Addr::new(10, 0, 0, 0), 24, used),
+            vec![(Ipv4Addr::new(10, 0, 0, 64), 26), (Ipv4Addr::new(10, 0, 0, 128), 25)]
+        );
+    }
+
+    #[test]
+    fn free_blocks_handles_used_block_at_top_of_address_space() {
+        // Regression test: a used subnet ending at 255.255.255.255 must not
+        // overflow the u32 cursor advance.
+        let used = vec![(Ipv4Addr::new(255, 255, 255, 255), 32)];
+        let blocks = free_blocks(Ipv4Addr::new(0, 0, 0, 0), 0, used);
+        assert_eq!(blocks.first(), Some(&(Ipv4Addr::new(0, 0, 0, 0), 1)));
+        assert_eq!(blocks.last(), Some(&(Ipv4Addr::new(255, 255, 255, 254), 32)));
+        assert_eq!(blocks.len(), 32);
+    }
+
+    #[test]
+    fn free_blocks_ignores_used_subnet_outside_base_range() {
+        // Regression test: a used subnet entirely outside the base network
+        // must not leak into the free-block output as if it bounded a gap.
+        let used = vec![(Ipv4Addr::new(192, 168, 1, 0), 24)];
+        let blocks = free_blocks(Ipv4Addr::new(10, 0, 0, 0), 24, used);
+        assert_eq!(blocks, vec![(Ipv4Addr::new(10, 0, 0, 0), 24)]);
+    }
+
+    #[test]
+    fn free_blocks_clamps_used_subnet_that_extends_past_base() {
+        // A used block that nests the base range but extends beyond it (CIDR
+        // blocks only ever nest or are disjoint, never partially overlap) is
+        // clamped to the base range, consuming all of it, rather than the
+        // unclamped broadcast leaking past base_broadcast into the free walk.
+        let used = vec![(Ipv4Addr::new(10, 0, 0, 0), 23)]; // 10.0.0.0 - 10.0.1.255
+        let blocks = free_blocks(Ipv4Addr::new(10, 0, 0, 0), 24, used);
+        assert!(blocks.is_empty());
+    }
 }
\ No newline at end of file